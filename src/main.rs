@@ -1,12 +1,33 @@
 use chrono::prelude::*;
-use chrono::{DateTime, Duration, Local, TimeZone};
-use std::process::Command;
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDateTime, TimeZone};
+use std::collections::{BTreeMap, HashSet};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
 struct GitCalendar {
     #[structopt(short = "a", long = "author")]
     author: Option<String>,
+
+    #[structopt(long = "since")]
+    since: Option<String>,
+
+    #[structopt(long = "until")]
+    until: Option<String>,
+
+    #[structopt(long = "branches", min_values = 0)]
+    branches: Option<Vec<String>>,
+
+    #[structopt(long = "color", default_value = "green")]
+    color: ColorScheme,
+
+    #[structopt(long = "no-truecolor")]
+    no_truecolor: bool,
+
+    #[structopt(long = "char", default_value = "\u{25fc}")]
+    glyph: char,
+
+    #[structopt(long = "ascii")]
+    ascii: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -18,65 +39,202 @@ enum CommitFreq {
     VeryHigh,
 }
 
-fn first_day() -> DateTime<Local> {
-    let today = Local::now();
-    let one_year_ago = Local
-        .ymd(today.year() - 1, today.month(), today.day())
-        .and_hms(0, 0, 0);
-    one_year_ago - Duration::days((one_year_ago.weekday() as i64) + 1)
+#[derive(Copy, Clone)]
+enum ColorScheme {
+    Green,
+    Red,
 }
 
-fn collect_commit_days(author: &Option<String>) -> Result<Vec<DateTime<Local>>, String> {
-    let mut args: Vec<String> = vec![
-        "log".to_string(),
-        "--no-merges".to_string(),
-        "--pretty=format:%at".to_string(),
-        "--since=13 months".to_string(),
-    ];
-    if let Some(name) = author {
-        args.push(format!("--author={}", name));
+impl ColorScheme {
+    fn palette(self) -> [(u8, u8, u8); 5] {
+        match self {
+            ColorScheme::Green => [
+                (22, 27, 34),
+                (14, 68, 41),
+                (0, 109, 50),
+                (38, 166, 65),
+                (57, 211, 83),
+            ],
+            ColorScheme::Red => [
+                (27, 22, 22),
+                (68, 14, 14),
+                (109, 0, 0),
+                (166, 38, 38),
+                (211, 57, 57),
+            ],
+        }
     }
+}
 
-    let ret = Command::new("git").args(&args).output();
-    let output = match ret {
-        Ok(o) => o,
-        Err(e) => return Err(e.to_string()),
-    };
+impl std::str::FromStr for ColorScheme {
+    type Err = String;
 
-    if !output.status.success() {
-        return Err("git log returns error".to_string());
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "green" => Ok(ColorScheme::Green),
+            "red" => Ok(ColorScheme::Red),
+            _ => Err(format!("unknown color scheme: {}", s)),
+        }
     }
+}
 
-    let output_str = match String::from_utf8(output.stdout) {
-        Ok(str) => str,
-        Err(e) => return Err(e.to_string()),
-    };
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| e.to_string())
+}
 
-    let commit_days: Vec<DateTime<Local>> = output_str
-        .lines()
-        .filter_map(|s| s.parse::<i64>().ok())
-        .map(|epoch| Local.timestamp(epoch, 0))
-        .collect();
+fn local_datetime(naive: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(dt) => Ok(dt),
+        LocalResult::Ambiguous(dt, _) => Ok(dt),
+        LocalResult::None => Err(format!(
+            "{} does not exist in the local timezone (DST gap)",
+            naive
+        )),
+    }
+}
 
-    Ok(commit_days)
+fn resolve_until(until: &Option<String>) -> Result<DateTime<Local>, String> {
+    match until {
+        Some(s) => {
+            let date = parse_date(s)?;
+            local_datetime(date.and_hms(23, 59, 59))
+        }
+        None => Ok(Local::now()),
+    }
 }
 
-fn count_commits_per_day(commit_days: &Vec<DateTime<Local>>) -> Vec<i32> {
-    let first = first_day();
-    let today = Local::now();
-    let last = Local
-        .ymd(today.year(), today.month(), today.day())
-        .and_hms(23, 59, 59);
-    let len = last.signed_duration_since(first).num_days();
+fn resolve_since(since: &Option<String>, until: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    match since {
+        Some(s) => {
+            let date = parse_date(s)?;
+            local_datetime(date.and_hms(0, 0, 0))
+        }
+        None => Ok(Local
+            .ymd(until.year() - 1, until.month(), until.day())
+            .and_hms(0, 0, 0)),
+    }
+}
 
-    let mut ret: Vec<i32> = vec![0; (len + 1) as usize];
-    for &day in commit_days {
-        let diff = last.signed_duration_since(day).num_days();
-        if diff >= len {
-            continue;
+fn first_day(since: DateTime<Local>) -> DateTime<Local> {
+    since - Duration::days(since.weekday().num_days_from_sunday() as i64)
+}
+
+fn resolve_tips(
+    repo: &gix::Repository,
+    branches: &Option<Vec<String>>,
+) -> Result<Vec<gix::ObjectId>, String> {
+    match branches {
+        None => {
+            let head = repo.head_id().map_err(|e| e.to_string())?;
+            Ok(vec![head.detach()])
+        }
+        Some(names) if !names.is_empty() => names
+            .iter()
+            .map(|name| {
+                repo.rev_parse_single(name.as_str())
+                    .map(|id| id.detach())
+                    .map_err(|e| e.to_string())
+            })
+            .collect(),
+        Some(_) => {
+            let mut ids = vec![];
+            for reference in repo
+                .references()
+                .map_err(|e| e.to_string())?
+                .local_branches()
+                .map_err(|e| e.to_string())?
+            {
+                let reference = reference.map_err(|e| e.to_string())?;
+                ids.push(reference.id().detach());
+            }
+            Ok(ids)
         }
+    }
+}
+
+fn author_matches(author: &gix::actor::SignatureRef<'_>, filter: &str) -> bool {
+    let filter = filter.to_lowercase();
+    author.name.to_string().to_lowercase().contains(&filter)
+        || author.email.to_string().to_lowercase().contains(&filter)
+}
+
+fn collect_commit_days(
+    author: &Option<String>,
+    branches: &Option<Vec<String>>,
+    since: DateTime<Local>,
+    until: DateTime<Local>,
+) -> Result<Vec<DateTime<Local>>, String> {
+    let repo = gix::discover(".").map_err(|e| e.to_string())?;
+    let tips = resolve_tips(&repo, branches)?;
+
+    let since_secs = since.timestamp();
+    let until_secs = until.timestamp();
 
-        ret[(len - diff) as usize] += 1;
+    let mut seen = HashSet::new();
+    let mut commit_days: Vec<DateTime<Local>> = vec![];
+
+    for tip in tips {
+        // Prune ancestry older than `since` as we walk instead of filtering after the
+        // fact, so a narrow --since/--until window on a long-lived repo doesn't still
+        // pay for a full-history traversal (same commit-time heuristic `git log
+        // --since` relies on).
+        let walk = repo
+            .rev_walk(Some(tip))
+            .sorting(
+                gix::traverse::commit::simple::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+                    seconds: since_secs,
+                },
+            )
+            .all()
+            .map_err(|e| e.to_string())?;
+
+        for info in walk {
+            let info = info.map_err(|e| e.to_string())?;
+            if !seen.insert(info.id) {
+                continue;
+            }
+
+            let commit = info.object().map_err(|e| e.to_string())?;
+            if commit.parent_ids().count() > 1 {
+                continue;
+            }
+
+            let commit_author = commit.author().map_err(|e| e.to_string())?;
+            if let Some(name) = author {
+                if !author_matches(&commit_author, name) {
+                    continue;
+                }
+            }
+
+            let seconds = commit_author.time.seconds;
+            if seconds < since_secs || seconds > until_secs {
+                continue;
+            }
+
+            commit_days.push(Local.timestamp(seconds, 0));
+        }
+    }
+
+    Ok(commit_days)
+}
+
+fn count_commits_per_day(
+    commit_days: &Vec<DateTime<Local>>,
+    first: DateTime<Local>,
+    until: DateTime<Local>,
+) -> Vec<i32> {
+    let mut by_date: BTreeMap<NaiveDate, i32> = BTreeMap::new();
+    for day in commit_days {
+        *by_date.entry(day.date_naive()).or_insert(0) += 1;
+    }
+
+    let last_date = until.date_naive();
+
+    let mut ret: Vec<i32> = vec![];
+    let mut date = first.date_naive();
+    while date <= last_date {
+        ret.push(*by_date.get(&date).unwrap_or(&0));
+        date += Duration::days(1);
     }
 
     ret
@@ -103,27 +261,61 @@ fn normalize_commits(commits: &Vec<i32>) -> Vec<CommitFreq> {
         .collect()
 }
 
-fn print_square(freq: CommitFreq) {
-    let color = match freq {
-        CommitFreq::No => 237,
-        CommitFreq::Low => 139,
-        CommitFreq::Mid => 40,
-        CommitFreq::High => 190,
-        CommitFreq::VeryHigh => 1,
+fn ansi256_approx(r: u8, g: u8, b: u8) -> u8 {
+    let to_idx = |v: u8| -> u8 { ((v as u16) * 5 / 255) as u8 };
+    16 + 36 * to_idx(r) + 6 * to_idx(g) + to_idx(b)
+}
+
+const ASCII_RAMP: [char; 5] = [' ', '.', ':', '+', '#'];
+
+fn print_square(freq: CommitFreq, scheme: ColorScheme, truecolor: bool, glyph: char, ascii: bool) {
+    let index = match freq {
+        CommitFreq::No => 0,
+        CommitFreq::Low => 1,
+        CommitFreq::Mid => 2,
+        CommitFreq::High => 3,
+        CommitFreq::VeryHigh => 4,
     };
-    let square = '\u{25fc}';
 
-    print!("\x1b[38;5;{}m{} \x1b[0m", color, square);
+    if ascii {
+        print!("{} ", ASCII_RAMP[index]);
+        return;
+    }
+
+    let (r, g, b) = scheme.palette()[index];
+    if truecolor {
+        print!("\x1b[38;2;{};{};{}m{} \x1b[0m", r, g, b, glyph);
+    } else {
+        print!("\x1b[38;5;{}m{} \x1b[0m", ansi256_approx(r, g, b), glyph);
+    }
+}
+
+fn month_span(first: DateTime<Local>, last: DateTime<Local>) -> i64 {
+    ((last.year() - first.year()) as i64) * 12 + (last.month() as i64 - first.month() as i64) + 1
+}
+
+// Width of the gap before the next month's header label. `day_of_month` maps to a
+// 0-indexed week-row (0..=4), so this can never underflow the way a 1-indexed
+// `ceil(day_of_month / 7)` did for days 29-31.
+fn header_pad(day_of_month: u32) -> usize {
+    let week_row = (day_of_month - 1) / 7;
+    4_usize.saturating_sub(week_row as usize)
 }
 
 impl GitCalendar {
     fn display(&self) -> Result<(), String> {
-        let commit_days = collect_commit_days(&self.author)?;
-        let commits = count_commits_per_day(&commit_days);
+        let until = resolve_until(&self.until)?;
+        let since = resolve_since(&self.since, until)?;
+        if since > until {
+            return Err("`--since` must not be after `--until`".to_string());
+        }
+
+        let commit_days = collect_commit_days(&self.author, &self.branches, since, until)?;
+        let first = first_day(since);
+        let commits = count_commits_per_day(&commit_days, first, until);
         let freqs = normalize_commits(&commits);
 
-        let first = first_day();
-        let last = Local::now();
+        let last = until;
 
         let diff = last.signed_duration_since(first);
         let days = diff.num_days() + 1;
@@ -139,16 +331,12 @@ impl GitCalendar {
         print!("    ");
         print!("{}", months[(first.month() - 1) as usize]);
 
-        let month_week = if first.day() % 7 == 0 {
-            first.day() / 7
-        } else {
-            first.day() / 7 + 1
-        };
-        print!("{}", " ".repeat(4_usize - month_week as usize));
+        print!("{}", " ".repeat(header_pad(first.day())));
 
+        let span = month_span(first, last);
         let mut month_str: Vec<&str> = vec![];
-        for i in 0..12 {
-            let index = (first.month() + i as u32) % 12;
+        for i in 1..span {
+            let index = (first.month() - 1 + i as u32) % 12;
             month_str.push(months[index as usize]);
         }
 
@@ -171,7 +359,13 @@ impl GitCalendar {
                     continue;
                 }
 
-                print_square(freqs[index]);
+                print_square(
+                    freqs[index],
+                    self.color,
+                    !self.no_truecolor,
+                    self.glyph,
+                    self.ascii,
+                );
             }
 
             println!("");
@@ -187,3 +381,84 @@ fn main() -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_accepts_iso8601() {
+        assert_eq!(parse_date("2026-01-02").unwrap(), NaiveDate::from_ymd(2026, 1, 2));
+    }
+
+    #[test]
+    fn parse_date_rejects_garbage() {
+        assert!(parse_date("not-a-date").is_err());
+    }
+
+    #[test]
+    fn resolve_until_defaults_to_end_of_requested_day() {
+        let until = resolve_until(&Some("2026-03-15".to_string())).unwrap();
+        assert_eq!(until.date_naive(), NaiveDate::from_ymd(2026, 3, 15));
+        assert_eq!(until.hour(), 23);
+        assert_eq!(until.minute(), 59);
+    }
+
+    #[test]
+    fn resolve_since_defaults_to_one_year_before_until() {
+        let until = resolve_until(&Some("2026-03-15".to_string())).unwrap();
+        let since = resolve_since(&None, until).unwrap();
+        assert_eq!(since.date_naive(), NaiveDate::from_ymd(2025, 3, 15));
+    }
+
+    #[test]
+    fn first_day_pads_back_to_the_preceding_sunday() {
+        // 2026-03-18 is a Wednesday.
+        let since = Local.ymd(2026, 3, 18).and_hms(0, 0, 0);
+        let first = first_day(since);
+        assert_eq!(first.weekday(), Weekday::Sun);
+        assert_eq!(first.date_naive(), NaiveDate::from_ymd(2026, 3, 15));
+    }
+
+    #[test]
+    fn first_day_is_a_no_op_when_already_sunday() {
+        let since = Local.ymd(2026, 3, 15).and_hms(0, 0, 0);
+        assert_eq!(first_day(since), since);
+    }
+
+    #[test]
+    fn count_commits_per_day_groups_by_calendar_date_not_epoch_diff() {
+        let first = Local.ymd(2026, 3, 1).and_hms(0, 0, 0);
+        let until = Local.ymd(2026, 3, 3).and_hms(23, 59, 59);
+        let commit_days = vec![
+            Local.ymd(2026, 3, 1).and_hms(23, 59, 0),
+            Local.ymd(2026, 3, 2).and_hms(0, 0, 1),
+            Local.ymd(2026, 3, 2).and_hms(12, 0, 0),
+        ];
+
+        let counts = count_commits_per_day(&commit_days, first, until);
+        assert_eq!(counts, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn count_commits_per_day_returns_zeros_when_there_are_no_commits() {
+        let first = Local.ymd(2026, 3, 1).and_hms(0, 0, 0);
+        let until = Local.ymd(2026, 3, 1).and_hms(23, 59, 59);
+        let counts = count_commits_per_day(&vec![], first, until);
+        assert_eq!(counts, vec![0]);
+    }
+
+    #[test]
+    fn header_pad_never_underflows_for_late_month_days() {
+        for day in 29..=31 {
+            assert_eq!(header_pad(day), 0);
+        }
+    }
+
+    #[test]
+    fn header_pad_matches_week_row_for_early_month_days() {
+        assert_eq!(header_pad(1), 4);
+        assert_eq!(header_pad(7), 4);
+        assert_eq!(header_pad(8), 3);
+    }
+}